@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
 use std::io::{stdin, stdout};
 
 use clap::Parser;
 use miette::{miette, Context, IntoDiagnostic, Result};
-use strois::{Bucket, Error, S3ErrorCode, UserError};
+use strois::{Bucket, Error, S3ErrorCode, UserError, WalkEntry};
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::{Date, OffsetDateTime, PrimitiveDateTime};
 
 pub fn get_styles() -> clap::builder::Styles {
     clap::builder::Styles::styled()
@@ -108,6 +111,30 @@ enum Command {
         #[clap(long, short, default_value_t = false)]
         force: bool,
     },
+    /// Print the number of objects and the total size under a prefix.
+    Du {
+        /// The prefix to measure, the whole bucket by default.
+        prefix: Option<String>,
+        /// Break the total down per sub-prefix, splitting the keys on this delimiter. Usually `/`.
+        #[clap(long, short)]
+        delimiter: Option<String>,
+    },
+    /// Print the keys under a prefix matching all the filters.
+    Find {
+        /// The prefix to search in, the whole bucket by default.
+        prefix: Option<String>,
+        /// Only print the objects whose name, the part of the key after the last `/`, matches
+        /// this glob. `*` matches any sequence of characters and `?` a single character.
+        #[clap(long)]
+        name: Option<String>,
+        /// Only print the objects modified after this date in UTC, e.g. `2024-01-01` or `2024-01-01T12:00:00`.
+        #[clap(long, value_parser = parse_date)]
+        newer_than: Option<OffsetDateTime>,
+        /// Only print the objects bigger (`+10M`), smaller (`-10M`) or exactly as big (`10M`) as this size.
+        /// The units are `K`, `M`, `G` and `T`, in powers of 1024. Without unit the size is in bytes.
+        #[clap(long, value_parser = parse_size_filter, allow_hyphen_values = true)]
+        size: Option<SizeFilter>,
+    },
     /// Commands related to the buckets.
     #[clap(aliases = &["b"], subcommand)]
     Bucket(BucketCommand),
@@ -186,12 +213,67 @@ fn main() -> Result<()> {
                 Some(content) => { s3.put_object(&path, content.as_bytes()).into_diagnostic()?;}
                 None if atty::isnt(atty::Stream::Stdin) => {
                     let mut reader = stdin();
-                    s3.put_object_multipart(path, &mut reader).into_diagnostic()?;
+                    s3.put_object_unknown_length(path, &mut reader).into_diagnostic()?;
                 }
                 None if force => { s3.put_object(&path, []).into_diagnostic()?; }
                 None => return Err(miette!("Did you forgot to pipe something in the command? If you wanted to reset the content of the file use `--force` or `-f`.")),
             }
         }
+        Command::Du { mut prefix, delimiter } => {
+            prefix.as_mut().map(sanitize_path);
+            let prefix = prefix.unwrap_or_default();
+            match delimiter {
+                None => {
+                    let (count, size) = s3.prefix_stats(&prefix).into_diagnostic()?;
+                    println!("{count}\t{}\t{prefix}", human_size(size));
+                }
+                Some(delimiter) => {
+                    let mut stats: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+                    for object in s3.list_objects(&prefix).into_diagnostic()? {
+                        let object = object.into_diagnostic()?;
+                        let rest = &object.key[prefix.len()..];
+                        // the objects directly under the prefix are grouped under the prefix itself
+                        let subprefix = match rest.find(delimiter.as_str()) {
+                            Some(index) => &object.key[..prefix.len() + index + delimiter.len()],
+                            None => prefix.as_str(),
+                        };
+                        let (count, size) = stats.entry(subprefix.to_string()).or_default();
+                        *count += 1;
+                        *size += object.size;
+                    }
+                    let (mut total_count, mut total_size) = (0, 0);
+                    for (subprefix, (count, size)) in stats {
+                        println!("{count}\t{}\t{subprefix}", human_size(size));
+                        total_count += count;
+                        total_size += size;
+                    }
+                    println!("{total_count}\t{}\ttotal", human_size(total_size));
+                }
+            }
+        }
+        Command::Find { mut prefix, name, newer_than, size } => {
+            prefix.as_mut().map(sanitize_path);
+            for entry in s3.walk(prefix.unwrap_or_default(), "") {
+                let object = match entry.into_diagnostic()? {
+                    WalkEntry::Object(object) => object,
+                    WalkEntry::Dir(_) => continue,
+                };
+                let file_name = object.key.rsplit('/').next().unwrap_or_default();
+                if name.as_ref().is_some_and(|name| !glob_match(name, file_name)) {
+                    continue;
+                }
+                if let Some(date) = newer_than {
+                    match OffsetDateTime::parse(&object.last_modified, &Rfc3339) {
+                        Ok(modified) if modified > date => (),
+                        _ => continue,
+                    }
+                }
+                if size.is_some_and(|size| !size.matches(object.size)) {
+                    continue;
+                }
+                println!("{}", object.key);
+            }
+        }
         Command::Bucket(command) => match command {
             BucketCommand::Create { ignore_if_exists } => {
                 match s3.create() {
@@ -212,9 +294,193 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Format a number of bytes with the biggest binary unit possible, `1.5KiB` for example.
+fn human_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size}{}", UNITS[unit])
+    } else {
+        format!("{size:.1}{}", UNITS[unit])
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SizeFilter {
+    Bigger(u64),
+    Smaller(u64),
+    Exactly(u64),
+}
+
+impl SizeFilter {
+    fn matches(self, size: u64) -> bool {
+        match self {
+            SizeFilter::Bigger(limit) => size > limit,
+            SizeFilter::Smaller(limit) => size < limit,
+            SizeFilter::Exactly(limit) => size == limit,
+        }
+    }
+}
+
+/// Parse a size filter like `+10M`, `-512K` or `42`.
+fn parse_size_filter(s: &str) -> std::result::Result<SizeFilter, String> {
+    let (filter, size): (fn(u64) -> SizeFilter, &str) = match s.strip_prefix('+') {
+        Some(size) => (SizeFilter::Bigger, size),
+        None => match s.strip_prefix('-') {
+            Some(size) => (SizeFilter::Smaller, size),
+            None => (SizeFilter::Exactly, s),
+        },
+    };
+    let (number, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => size.split_at(index),
+        None => (size, ""),
+    };
+    let multiplier: u64 = match unit {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        "T" => 1024 * 1024 * 1024 * 1024,
+        unit => return Err(format!("Unknown unit `{unit}`, use `K`, `M`, `G` or `T`.")),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size `{s}`, use something like `+10M`."))?;
+    number
+        .checked_mul(multiplier)
+        .map(filter)
+        .ok_or_else(|| format!("The size `{s}` is too big."))
+}
+
+/// Parse a date like `2024-01-01` or `2024-01-01T12:00:00`, in UTC.
+fn parse_date(s: &str) -> std::result::Result<OffsetDateTime, String> {
+    let date = if s.contains('T') {
+        PrimitiveDateTime::parse(s, &Iso8601::DEFAULT)
+    } else {
+        Date::parse(s, &Iso8601::DEFAULT).map(Date::midnight)
+    };
+    date.map(PrimitiveDateTime::assume_utc).map_err(|e| {
+        format!("Invalid date `{s}`: {e}, use the `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS` format.")
+    })
+}
+
+/// Match a text against a glob where `*` matches any sequence of characters and `?` a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` and of the text it was matched against, to backtrack on a mismatch.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 fn sanitize_path(path: &mut String) {
     if path.starts_with('/') {
         log::warn!("Invalid path, trimming the `/` at the starts of your path");
         *path = path.trim_start_matches('/').to_string();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*.json", "kero.json"));
+        assert!(glob_match("*.json", ".json"));
+        assert!(!glob_match("*.json", "kero.json.gz"));
+        assert!(glob_match("tamo-??.log", "tamo-42.log"));
+        assert!(!glob_match("tamo-??.log", "tamo-4.log"));
+        assert!(glob_match("*kero*", "tamokerokefir"));
+        // The `*` must backtrack to match the last occurrence.
+        assert!(glob_match("*a*b", "aaab"));
+        assert!(!glob_match("*a*b", "aaba"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+        assert!(glob_match("été?", "étés"));
+    }
+
+    #[test]
+    fn size_filter() {
+        assert!(matches!(
+            parse_size_filter("42"),
+            Ok(SizeFilter::Exactly(42))
+        ));
+        assert!(matches!(
+            parse_size_filter("+10M"),
+            Ok(SizeFilter::Bigger(10_485_760))
+        ));
+        assert!(matches!(
+            parse_size_filter("-512K"),
+            Ok(SizeFilter::Smaller(524_288))
+        ));
+        assert!(matches!(
+            parse_size_filter("1T"),
+            Ok(SizeFilter::Exactly(1_099_511_627_776))
+        ));
+        assert_eq!(
+            parse_size_filter("10Z").unwrap_err(),
+            "Unknown unit `Z`, use `K`, `M`, `G` or `T`."
+        );
+        assert_eq!(
+            parse_size_filter("+M").unwrap_err(),
+            "Invalid size `+M`, use something like `+10M`."
+        );
+        assert_eq!(
+            parse_size_filter("99999999T").unwrap_err(),
+            "The size `99999999T` is too big."
+        );
+
+        assert!(SizeFilter::Bigger(10).matches(11));
+        assert!(!SizeFilter::Bigger(10).matches(10));
+        assert!(SizeFilter::Smaller(10).matches(9));
+        assert!(!SizeFilter::Exactly(10).matches(9));
+    }
+
+    #[test]
+    fn date() {
+        let date = parse_date("2024-01-01").unwrap();
+        assert_eq!(date.unix_timestamp(), 1_704_067_200);
+        let date = parse_date("2024-01-01T12:30:00").unwrap();
+        assert_eq!(date.unix_timestamp(), 1_704_112_200);
+        // The dates are compared to the last modified dates returned by S3.
+        let modified = OffsetDateTime::parse("2024-01-01T12:30:00.001Z", &Rfc3339).unwrap();
+        assert!(modified > date);
+
+        for invalid in [
+            "2024-13-01",
+            "2024-02-30",
+            "2024-01-01T25:00:00",
+            "01/01/2024",
+            "tamo",
+        ] {
+            assert!(parse_date(invalid).is_err(), "{invalid}");
+        }
+    }
+}
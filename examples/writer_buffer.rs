@@ -0,0 +1,93 @@
+//! Compare the buffer sizes of `Bucket::get_object_to_writer_buffered` when downloading a big
+//! object to a writer paying a fixed cost on every call, like a pipe or a socket.
+//!
+//! The object is served by a minimal HTTP server on localhost so the network doesn't hide the
+//! difference: `cargo run --release --example writer_buffer`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use strois::Bucket;
+
+const OBJECT_SIZE: usize = 64 * 1024 * 1024;
+/// The cost of a single call to the writer.
+const WRITE_COST: Duration = Duration::from_micros(20);
+
+/// A writer discarding everything, slowly.
+struct SlowWriter {
+    calls: usize,
+}
+
+impl Write for SlowWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        let start = Instant::now();
+        while start.elapsed() < WRITE_COST {
+            std::hint::spin_loop();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Answer every request with an object of `OBJECT_SIZE` bytes.
+fn serve() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let body = std::sync::Arc::new(vec![b'k'; OBJECT_SIZE]);
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let body = body.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    // Skip the request head.
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {OBJECT_SIZE}\r\n\r\n");
+                    if stream.write_all(head.as_bytes()).is_err()
+                        || stream.write_all(&body).is_err()
+                    {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    addr
+}
+
+fn main() {
+    let addr = serve();
+    let bucket = Bucket::builder(format!("http://{addr}"))
+        .unwrap()
+        .key("minioadmin")
+        .secret("minioadmin")
+        .with_url_path_style(true)
+        .bucket("tamo")
+        .unwrap();
+
+    println!("buffer\twrites\ttime");
+    for buffer_size in [8 * 1024, 64 * 1024, 1024 * 1024, 8 * 1024 * 1024] {
+        let mut writer = SlowWriter { calls: 0 };
+        let start = Instant::now();
+        let size = bucket
+            .get_object_to_writer_buffered("tamo", &mut writer, buffer_size)
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(size, OBJECT_SIZE as u64);
+        println!("{}KiB\t{}\t{elapsed:.2?}", buffer_size / 1024, writer.calls);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Bucket, CacheOutcome, Result, UserError};
+
+/// The configuration of a [`CachedBucket`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// The maximum number of bytes kept in memory. 64MiB by default.
+    pub max_size: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            max_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+}
+
+/// A [`Bucket`] keeping the objects it downloads in memory, see [`Bucket::with_cache`].
+///
+/// Every read sends a conditional request to S3 with the ETag of the cached object.
+/// S3 only sends the object back if it was modified, otherwise the cached object is returned.
+/// When the cache is full, the least recently used objects are evicted.
+/// The clones of a `CachedBucket` share the same cache.
+#[derive(Debug, Clone)]
+pub struct CachedBucket {
+    bucket: Bucket,
+    config: CacheConfig,
+    cache: Arc<Mutex<Cache>>,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    entries: HashMap<String, Entry>,
+    size: usize,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    etag: String,
+    content: Vec<u8>,
+    last_used: u64,
+}
+
+impl CachedBucket {
+    pub(crate) fn new(bucket: Bucket, config: CacheConfig) -> Self {
+        CachedBucket {
+            bucket,
+            config,
+            cache: Arc::default(),
+        }
+    }
+
+    /// Return the underlying [`Bucket`].
+    pub fn bucket(&self) -> &Bucket {
+        &self.bucket
+    }
+
+    /// Get an object as raw bytes, from the cache if it wasn't modified on S3.
+    ///
+    /// # Example
+    /// ```
+    /// use strois::{Builder, CacheConfig};
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// bucket.put_object("tamo", "kero")?;
+    ///
+    /// let cached = bucket.with_cache(CacheConfig::new().max_size(1024 * 1024));
+    /// // The first call downloads the object, the second one only checks its ETag.
+    /// assert_eq!(cached.get_object_bytes("tamo")?, b"kero");
+    /// assert_eq!(cached.get_object_bytes("tamo")?, b"kero");
+    ///
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn get_object_bytes(&self, path: impl AsRef<str>) -> Result<Vec<u8>> {
+        let path = path.as_ref();
+        let cached_etag = self.lock().etag(path);
+
+        match self
+            .bucket
+            .get_object_cached(path, cached_etag.as_deref())?
+        {
+            CacheOutcome::Unchanged => match self.lock().get(path) {
+                Some(content) => Ok(content),
+                // The object was evicted in the meantime.
+                None => self.bucket.get_object_bytes(path),
+            },
+            CacheOutcome::Updated { bytes, etag } => {
+                if let Some(etag) = etag {
+                    self.lock()
+                        .insert(path, etag, bytes.clone(), self.config.max_size);
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Get an object as a string, from the cache if it wasn't modified on S3.
+    pub fn get_object_string(&self, path: impl AsRef<str>) -> Result<String> {
+        let bytes = self.get_object_bytes(path)?;
+        Ok(String::from_utf8(bytes).map_err(UserError::PayloadCouldNotBeConvertedToString)?)
+    }
+
+    /// Remove an object from the cache.
+    pub fn invalidate(&self, path: impl AsRef<str>) {
+        self.lock().remove(path.as_ref());
+    }
+
+    /// Remove all the objects from the cache.
+    pub fn clear(&self) {
+        *self.lock() = Cache::default();
+    }
+
+    /// Return the number of bytes currently cached.
+    pub fn cached_size(&self) -> usize {
+        self.lock().size
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        // The cache is always left in a consistent state, even if a thread panicked.
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Cache {
+    fn etag(&self, path: &str) -> Option<String> {
+        self.entries.get(path).map(|entry| entry.etag.clone())
+    }
+
+    fn get(&mut self, path: &str) -> Option<Vec<u8>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(path)?;
+        entry.last_used = self.clock;
+        Some(entry.content.clone())
+    }
+
+    fn insert(&mut self, path: &str, etag: String, content: Vec<u8>, max_size: usize) {
+        self.remove(path);
+        if content.len() > max_size {
+            return;
+        }
+        while self.size + content.len() > max_size {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone());
+            match oldest {
+                Some(oldest) => self.remove(&oldest),
+                None => break,
+            }
+        }
+
+        self.clock += 1;
+        self.size += content.len();
+        let entry = Entry {
+            etag,
+            content,
+            last_used: self.clock,
+        };
+        self.entries.insert(path.to_string(), entry);
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.size -= entry.content.len();
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::{fmt, io::Read};
+
+use http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use ureq::Response;
+
+use crate::{Result, UserError};
+
+/// The compression formats strois can decompress on the fly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Requires the `gzip` feature.
+    Gzip,
+    /// Requires the `zstd` feature.
+    Zstd,
+}
+
+impl Compression {
+    /// The value of the `Accept-Encoding` header sent by
+    /// [`Bucket::get_object_reader_negotiated`](crate::Bucket::get_object_reader_negotiated),
+    /// only the enabled formats are accepted.
+    pub(crate) const ACCEPT_ENCODING: &'static str =
+        match (cfg!(feature = "gzip"), cfg!(feature = "zstd")) {
+            (true, true) => "gzip, zstd",
+            (true, false) => "gzip",
+            (false, true) => "zstd",
+            (false, false) => "identity",
+        };
+
+    /// Guess the compression of an object from its `Content-Encoding` or `Content-Type` headers,
+    /// and then from the extension of its key.
+    pub(crate) fn detect(path: &str, response: &Response) -> Option<Self> {
+        let encoding = response.header(CONTENT_ENCODING.as_str());
+        let content_type = response.header(CONTENT_TYPE.as_str()).unwrap_or_default();
+
+        match (encoding.and_then(Self::from_content_encoding), content_type) {
+            (Some(compression), _) => Some(compression),
+            (_, "application/gzip" | "application/x-gzip") => Some(Compression::Gzip),
+            (_, "application/zstd") => Some(Compression::Zstd),
+            _ if path.ends_with(".gz") => Some(Compression::Gzip),
+            _ if path.ends_with(".zst") => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The compression of a body from its `Content-Encoding` header.
+    pub(crate) fn from_content_encoding(encoding: &str) -> Option<Self> {
+        match encoding {
+            "gzip" | "x-gzip" => Some(Compression::Gzip),
+            "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Wrap the reader in a decoder of this compression format.
+    pub(crate) fn decoder(
+        self,
+        reader: impl Read + Send + 'static,
+    ) -> Result<Box<dyn Read + Send + 'static>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Box::new(zstd::Decoder::new(reader)?)),
+            #[allow(unreachable_patterns)]
+            compression => {
+                drop(reader);
+                Err(UserError::CompressionFeatureDisabled(compression).into())
+            }
+        }
+    }
+
+    /// The cargo feature required to decompress this format.
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
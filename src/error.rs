@@ -1,8 +1,14 @@
-use std::{fmt, io::BufReader, string::FromUtf8Error};
+use std::{collections::HashMap, fmt, io::Read, string::FromUtf8Error};
 
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use time::OffsetDateTime;
+
+use crate::Compression;
+
+/// The maximum number of bytes read from the body of an error sent by S3.
+const MAX_ERROR_BODY_SIZE: u64 = 64 * 1024;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -20,6 +26,183 @@ pub enum Error {
     RustyS3(#[from] rusty_s3::BucketError),
     #[error(transparent)]
     Url(#[from] url::ParseError),
+    #[error("Failed after successfully processing {done} objects: {source}")]
+    PartialFailure { done: usize, source: Box<Error> },
+    #[error("The object `{path}` was modified, it doesn't match the ETag `{etag}` anymore.")]
+    ObjectModified { path: String, etag: String },
+    #[error("The copy of `{from}` to `{to}` has the ETag `{copied}` instead of `{expected}`.")]
+    CopyMismatch {
+        from: String,
+        to: String,
+        expected: String,
+        copied: String,
+    },
+    #[error("The object `{path}` was stored with the ETag `{etag}` but the MD5 of its content is `{expected}`.")]
+    EtagMismatch {
+        path: String,
+        expected: String,
+        etag: String,
+    },
+    #[cfg(feature = "checksum")]
+    #[error("The object `{path}` was stored with the SHA256 checksum `{checksum}` but the checksum of the downloaded content is `{expected}`.")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        checksum: String,
+    },
+    #[cfg(feature = "checksum")]
+    #[error(
+        "The object `{path}` has no SHA256 checksum of its whole content to verify it against."
+    )]
+    MissingChecksum { path: String },
+    #[error("The object `{path}` is bigger than the limit of {max_bytes} bytes.")]
+    ObjectTooLarge { path: String, max_bytes: u64 },
+    #[error("The deadline to get the object `{path}` was exceeded.")]
+    DeadlineExceeded { path: String },
+    #[error("The bucket `{bucket}` doesn't have object lock enabled, it can only be enabled when creating the bucket.")]
+    ObjectLockDisabled { bucket: String },
+    #[error("The object `{path}` redirects to `{location}`, outside of the bucket.")]
+    ExternalRedirect { path: String, location: String },
+    #[error("Too many redirects while getting `{path}`.")]
+    TooManyRedirects { path: String },
+    #[error(
+        "The download of `{path}` was truncated: received {received} bytes out of {expected}."
+    )]
+    TruncatedDownload {
+        path: String,
+        expected: u64,
+        received: u64,
+    },
+    #[cfg(feature = "json")]
+    #[error("The object `{path}` {}: {source}. Near: `{snippet}`.", json_error_kind(.source))]
+    InvalidJson {
+        path: String,
+        /// The part of the object around the error.
+        snippet: String,
+        source: serde_json::Error,
+    },
+    #[error("The clock of this machine is off by {skew} compared to S3: it's {local} locally but {server} on S3. Synchronize your clock or enable `Builder::compensate_clock_skew`.")]
+    ClockSkew {
+        local: OffsetDateTime,
+        server: OffsetDateTime,
+        skew: time::Duration,
+    },
+}
+
+/// Tell apart an object that isn't JSON from a JSON object with the wrong shape.
+#[cfg(feature = "json")]
+fn json_error_kind(error: &serde_json::Error) -> &'static str {
+    match error.classify() {
+        serde_json::error::Category::Data => "is valid JSON but doesn't have the expected shape",
+        serde_json::error::Category::Eof => "is truncated JSON",
+        serde_json::error::Category::Syntax | serde_json::error::Category::Io => {
+            "is not valid JSON"
+        }
+    }
+}
+
+impl Error {
+    /// Return the HTTP status code returned by S3 if there was one.
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            Error::S3Error(error) => Some(error.status_code),
+            _ => None,
+        }
+    }
+
+    /// Display the error followed by all its causes, separated by `: `.
+    /// The causes already included in the message of the previous error are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use strois::{Error, UserError};
+    ///
+    /// let error = String::from_utf8(vec![0xff]).unwrap_err();
+    /// let error = Error::from(UserError::PayloadCouldNotBeConvertedToString(error));
+    /// let error = Error::PartialFailure { done: 3, source: Box::new(error) };
+    /// assert_eq!(
+    ///     error.display_chain(),
+    ///     "Failed after successfully processing 3 objects: Payload could not be converted to utf-8 string: `invalid utf-8 sequence of 1 bytes from index 0`.",
+    /// );
+    /// ```
+    pub fn display_chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            let cause = error.to_string();
+            if !message.contains(&cause) {
+                message.push_str(": ");
+                message.push_str(&cause);
+            }
+            source = error.source();
+        }
+        message
+    }
+
+    /// Return the [`S3ErrorCode`] returned by S3 if there was one.
+    pub fn s3_code(&self) -> Option<S3ErrorCode> {
+        match self {
+            Error::S3Error(error) => Some(error.code),
+            _ => None,
+        }
+    }
+
+    /// Return the region S3 redirected us to when the bucket lives in another region.
+    pub fn redirect_region(&self) -> Option<&str> {
+        match self {
+            Error::S3Error(error) if error.status_code.is_redirection() => error.region.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if the error is transient and the request can be sent again.
+    /// That's the case of the throttling and server errors, timeouts and network failures.
+    ///
+    /// # Example
+    /// ```
+    /// use strois::Builder;
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// let error = bucket.get_object_string("does-not-exist").unwrap_err();
+    /// assert!(!error.is_retryable());
+    /// assert_eq!(error.status_code(), Some(http::StatusCode::NOT_FOUND));
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::S3Error(error) => {
+                matches!(
+                    error.code,
+                    S3ErrorCode::SlowDown
+                        | S3ErrorCode::ServiceUnavailable
+                        | S3ErrorCode::RequestTimeout
+                        | S3ErrorCode::OperationAborted
+                ) || error.status_code.is_server_error()
+                    || error.status_code == StatusCode::TOO_MANY_REQUESTS
+            }
+            Error::HttpError(error) => matches!(
+                error.kind(),
+                ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Io
+            ),
+            Error::IoError(error) => matches!(
+                error.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            Error::TruncatedDownload { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<S3Error> for Error {
@@ -32,12 +215,30 @@ impl From<ureq::Error> for Error {
     fn from(error: ureq::Error) -> Self {
         match error {
             ureq::Error::Status(code, response) => {
-                let reader = BufReader::new(response.into_reader());
-                let mut error: S3Error = match quick_xml::de::from_reader(reader) {
-                    Ok(error) => error,
-                    Err(e) => return Error::InternalError(InternalError::BadS3Payload(e)),
+                let status_code = StatusCode::try_from(code).unwrap();
+                let request_id = response.header("x-amz-request-id").map(String::from);
+                let region = response.header("x-amz-bucket-region").map(String::from);
+                // A huge error body must not exhaust the memory, the errors of S3 are tiny.
+                let mut body = String::new();
+                if let Err(e) = response
+                    .into_reader()
+                    .take(MAX_ERROR_BODY_SIZE)
+                    .read_to_string(&mut body)
+                {
+                    return Error::InternalError(InternalError::S3ReturnedNonUtf8Payload(e));
+                }
+                // The responses to the `HEAD` requests don't contain any body.
+                let mut error = if body.is_empty() {
+                    S3Error::from_status_code(status_code, request_id, region.clone())
+                } else {
+                    match S3Error::parse(&body) {
+                        Ok(error) => error,
+                        Err(e) => return Error::InternalError(InternalError::BadS3Payload(e)),
+                    }
                 };
-                error.status_code = StatusCode::try_from(code).unwrap();
+                error.status_code = status_code;
+                // Some S3-compatible services only send the region in the body.
+                error.region = region.or_else(|| error.extra.get("Region").cloned());
                 Error::S3Error(Box::new(error))
             }
             e => Error::HttpError(Box::new(e)),
@@ -48,24 +249,57 @@ impl From<ureq::Error> for Error {
 #[derive(Debug, Error)]
 pub enum UserError {
     #[error("Payload could not be converted to utf-8 string: `{0}`.")]
-    PayloadCouldNotBeConvertedToString(FromUtf8Error),
+    PayloadCouldNotBeConvertedToString(#[source] FromUtf8Error),
+    #[cfg(feature = "encoding")]
+    #[error("Payload could not be decoded with its declared charset `{0}`.")]
+    PayloadCouldNotBeDecoded(String),
     #[error("Tried to send more than 10_000 parts in a multipart upload. Reduce the size of your object or send bigger parts.")]
     TriedToSendMoreThan10000PartsInMultiPart,
+    #[error("Tried to send an object of {0} bytes in a single request but S3 refuses objects bigger than 5GiB. Use a multipart upload instead.")]
+    ObjectTooLargeForSinglePut(usize),
+    #[error("The multipart size is set to {size} bytes but S3 refuses the parts smaller than {min} bytes, except the last one.")]
+    MultipartSizeTooSmall { size: usize, min: usize },
+    #[error("The part {part} of the multipart upload was {size} bytes, only the last part can be smaller than {min} bytes.")]
+    PartTooSmall { part: u16, size: usize, min: usize },
+    #[error("The parts of the multipart state must be numbered from 1 to {count} without gaps or duplicates, but the part {number} was found.")]
+    InvalidMultipartState { count: usize, number: u16 },
+    #[error("Can't copy the prefix `{src}` to `{dst}` since one contains the other.")]
+    OverlappingPrefixes { src: String, dst: String },
+    #[error("The range `{start}..{end}` goes beyond the end of the object.")]
+    RangeOutOfBounds { start: u64, end: u64 },
+    #[error("The endpoint `{0}` is an `s3://` URI, pass the HTTP(S) endpoint of the S3 service instead, e.g. `https://s3.us-east-1.amazonaws.com`, and the name of the bucket separately.")]
+    S3UriAsEndpoint(String),
+    #[error("The endpoint `{endpoint}` uses the `{scheme}` scheme, only `http` and `https` are supported.")]
+    InvalidEndpointScheme { scheme: String, endpoint: String },
+    #[error("The connection URL `{url}` is invalid because {reason}.")]
+    InvalidConnectionUrl { url: String, reason: &'static str },
+    #[error("The bucket name `{name}` is invalid because {reason}.")]
+    InvalidBucketName { name: String, reason: &'static str },
+    #[error(
+        "The region `{0}` is invalid, it can only contain lowercase letters, numbers and hyphens."
+    )]
+    InvalidRegion(String),
+    #[error("The key `{key}` is invalid because {reason}.")]
+    InvalidKey { key: String, reason: &'static str },
+    #[error("The object is compressed with {0} but strois was compiled without the `{}` feature.", .0.feature())]
+    CompressionFeatureDisabled(Compression),
 }
 
 #[derive(Debug, Error)]
 pub enum InternalError {
     #[error("S3 returned non utf8 payload, this shouldn't be possible: `{0}`.`")]
-    S3ReturnedNonUtf8Payload(std::io::Error),
+    S3ReturnedNonUtf8Payload(#[source] std::io::Error),
     #[error("Could not deserialize S3 payload: `{0}`.`")]
-    BadS3Payload(quick_xml::de::DeError),
+    BadS3Payload(#[source] quick_xml::de::DeError),
     #[error("Multipart missing Etag header: `{0}`")]
     MultipartMissingEtagHeader(String),
+    #[error("S3 returned an invalid date: `{0}`.")]
+    BadS3Date(String),
 }
 
 #[derive(Debug, Error, Deserialize)]
 #[serde(rename_all = "PascalCase")]
-#[error("{code}: {} on {bucket_name:?}", message.as_deref().unwrap_or_default())]
+#[error("{code}: {} on {bucket_name:?}{}", message.as_deref().unwrap_or_default(), code.hint().map(|hint| format!(". Hint: {hint}")).unwrap_or_default())]
 pub struct S3Error {
     #[serde(skip)]
     pub status_code: StatusCode,
@@ -76,6 +310,83 @@ pub struct S3Error {
     pub resource: Option<String>,
     pub request_id: Option<String>,
     pub host_id: Option<String>,
+    /// The endpoint to use when the bucket lives in another region.
+    pub endpoint: Option<String>,
+    /// The region of the bucket, sent by S3 when it lives in another region.
+    #[serde(skip)]
+    pub region: Option<String>,
+    /// The fields of the error that aren't modeled above, e.g. `MaxAllowedSize` for an
+    /// [`S3ErrorCode::EntityTooLarge`] or `Condition` for an [`S3ErrorCode::PreconditionFailed`].
+    #[serde(skip)]
+    pub extra: HashMap<String, String>,
+    /// The XML sent by S3, if there was one.
+    #[serde(skip)]
+    pub xml: Option<String>,
+}
+
+impl S3Error {
+    /// The fields parsed into the named fields of the error.
+    const KNOWN_FIELDS: [&'static str; 7] = [
+        "Code",
+        "Message",
+        "BucketName",
+        "Resource",
+        "RequestId",
+        "HostId",
+        "Endpoint",
+    ];
+
+    /// Parse the XML error sent by S3 while keeping its unknown fields and the XML itself.
+    pub(crate) fn parse(xml: &str) -> std::result::Result<Self, quick_xml::de::DeError> {
+        let mut error: S3Error = quick_xml::de::from_str(xml)?;
+        // The unknown fields are kept on a best effort basis, they could contain nested elements.
+        if let Ok(mut fields) = quick_xml::de::from_str::<HashMap<String, String>>(xml) {
+            fields.retain(|name, _| !Self::KNOWN_FIELDS.contains(&name.as_str()));
+            error.extra = fields;
+        }
+        error.xml = Some(xml.to_string());
+        Ok(error)
+    }
+
+    /// Guess the error from the status code when S3 didn't send any payload.
+    fn from_status_code(
+        status_code: StatusCode,
+        request_id: Option<String>,
+        region: Option<String>,
+    ) -> Self {
+        let code = match status_code {
+            StatusCode::MOVED_PERMANENTLY => S3ErrorCode::PermanentRedirect,
+            StatusCode::FOUND => S3ErrorCode::Redirect,
+            StatusCode::TEMPORARY_REDIRECT => S3ErrorCode::TemporaryRedirect,
+            StatusCode::FORBIDDEN => S3ErrorCode::AccessDenied,
+            StatusCode::NOT_FOUND => S3ErrorCode::NoSuchKey,
+            StatusCode::PRECONDITION_FAILED => S3ErrorCode::PreconditionFailed,
+            StatusCode::RANGE_NOT_SATISFIABLE => S3ErrorCode::InvalidRange,
+            StatusCode::SERVICE_UNAVAILABLE => S3ErrorCode::ServiceUnavailable,
+            _ => S3ErrorCode::Unknown,
+        };
+
+        let message = match &region {
+            Some(region) if status_code.is_redirection() => Some(format!(
+                "The bucket lives in the `{region}` region, send the request to this region instead."
+            )),
+            _ => None,
+        };
+
+        S3Error {
+            status_code,
+            code,
+            message,
+            bucket_name: None,
+            resource: None,
+            request_id,
+            host_id: None,
+            endpoint: None,
+            region,
+            extra: HashMap::new(),
+            xml: None,
+        }
+    }
 }
 
 #[derive(Debug, Error, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
@@ -133,10 +444,12 @@ pub enum S3ErrorCode {
     NoSuchBucketPolicy,
     NoSuchKey,
     NoSuchLifecycleConfiguration,
+    NoSuchObjectLockConfiguration,
     NoSuchUpload,
     NoSuchVersion,
     NotImplemented,
     NotSignedUp,
+    ObjectLockConfigurationNotFoundError,
     OperationAborted,
     PermanentRedirect,
     PreconditionFailed,
@@ -161,9 +474,123 @@ pub enum S3ErrorCode {
     Unknown,
 }
 
+impl S3ErrorCode {
+    /// Return a hint on how to fix the most common errors.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            S3ErrorCode::AccessDenied => Some("Check your credentials, the region of the bucket and its policy. If the clock of this machine is out of sync, S3 may also refuse the request, look for a `RequestTimeTooSkewed` error."),
+            S3ErrorCode::SignatureDoesNotMatch => Some("Check your secret key and the region of the bucket. A clock out of sync with S3 can also invalidate the signature."),
+            S3ErrorCode::InvalidAccessKeyId => Some("Check your access key, it doesn't exist on this S3 instance."),
+            S3ErrorCode::RequestTimeTooSkewed => Some("Synchronize the clock of this machine or enable `Builder::compensate_clock_skew`."),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for S3ErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = quick_xml::se::to_string(self).expect("This can't fail");
         write!(f, "{}", &s[1..s.len() - 2])
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn source_chain() {
+        let utf8 = || String::from_utf8(vec![0xff]).unwrap_err();
+
+        let error = Error::from(UserError::PayloadCouldNotBeConvertedToString(utf8()));
+        assert!(error.source().unwrap().is::<FromUtf8Error>());
+
+        let io = std::io::Error::other("kero");
+        let error = Error::from(InternalError::S3ReturnedNonUtf8Payload(io));
+        assert!(error.source().unwrap().is::<std::io::Error>());
+
+        let error = Error::PartialFailure {
+            done: 0,
+            source: Box::new(UserError::PayloadCouldNotBeConvertedToString(utf8()).into()),
+        };
+        let source = error.source().unwrap();
+        // thiserror exposes the box itself as the source.
+        assert!(source.is::<Box<Error>>());
+        assert!(source.source().unwrap().is::<FromUtf8Error>());
+
+        // The transparent variants forward to the source of the error they wrap.
+        let error = Error::from(S3Error::from_status_code(StatusCode::NOT_FOUND, None, None));
+        assert!(error.source().is_none());
+        let error = Error::from(ureq::get("http://127.0.0.1:1").call().unwrap_err());
+        assert!(matches!(error, Error::HttpError(_)));
+        assert!(error.source().unwrap().is::<std::io::Error>());
+        let error = Error::from(std::io::Error::other("kero"));
+        assert!(error.source().is_none());
+        let user_error = UserError::PayloadCouldNotBeConvertedToString(utf8());
+        let error = Error::from(std::io::Error::other(user_error));
+        assert!(error.source().unwrap().is::<FromUtf8Error>());
+        let error = Error::from(rusty_s3::BucketError::MissingHost);
+        assert!(matches!(error, Error::RustyS3(_)));
+        assert!(error.source().is_none());
+        let error = Error::from(url::Url::parse("kero").unwrap_err());
+        assert!(matches!(error, Error::Url(_)));
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn s3_error_extra_fields() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error><Code>EntityTooLarge</Code><Message>Your proposed upload exceeds the maximum allowed object size.</Message><ProposedSize>6442450944</ProposedSize><MaxSizeAllowed>5368709120</MaxSizeAllowed><RequestId>4442587FB7D0A2F9</RequestId><HostId>kero</HostId></Error>"#;
+        let error = S3Error::parse(xml).unwrap();
+        assert_eq!(error.code, S3ErrorCode::EntityTooLarge);
+        assert_eq!(error.request_id.as_deref(), Some("4442587FB7D0A2F9"));
+        let mut extra: Vec<_> = error.extra.iter().collect();
+        extra.sort();
+        insta::assert_debug_snapshot!(extra, @r###"
+        [
+            (
+                "MaxSizeAllowed",
+                "5368709120",
+            ),
+            (
+                "ProposedSize",
+                "6442450944",
+            ),
+        ]
+        "###);
+        assert_eq!(error.xml.as_deref(), Some(xml));
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn invalid_json() {
+        let invalid_json = |json: &str| {
+            let source = serde_json::from_str::<HashMap<String, u32>>(json).unwrap_err();
+            Error::InvalidJson {
+                path: "tamo".to_string(),
+                snippet: json.to_string(),
+                source,
+            }
+        };
+        insta::assert_display_snapshot!(invalid_json("{ \"kero\": 3,, }"), @r###"The object `tamo` is not valid JSON: key must be a string at line 1 column 13. Near: `{ "kero": 3,, }`."###);
+        insta::assert_display_snapshot!(invalid_json("{ \"kero\": 3"), @r###"The object `tamo` is truncated JSON: EOF while parsing an object at line 1 column 11. Near: `{ "kero": 3`."###);
+        insta::assert_display_snapshot!(invalid_json("{ \"kero\": \"frog\" }"), @r###"The object `tamo` is valid JSON but doesn't have the expected shape: invalid type: string "frog", expected u32 at line 1 column 16. Near: `{ "kero": "frog" }`."###);
+    }
+
+    #[test]
+    fn display_chain() {
+        let error = Error::from(ureq::get("http://127.0.0.1:1").call().unwrap_err());
+        assert!(error.display_chain().starts_with(&error.to_string()));
+
+        let error = Error::PartialFailure {
+            done: 2,
+            source: Box::new(Error::ObjectModified {
+                path: String::from("tamo"),
+                etag: String::from("kero"),
+            }),
+        };
+        insta::assert_display_snapshot!(error.display_chain(), @"Failed after successfully processing 2 objects: The object `tamo` was modified, it doesn't match the ETag `kero` anymore.");
+    }
+}
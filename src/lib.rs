@@ -2,12 +2,34 @@
 
 mod bucket;
 mod builder;
+mod cache;
 mod client;
+mod compression;
 mod error;
+mod lock;
+mod object;
+mod parallel;
+mod post;
+#[cfg(feature = "object_store")]
+mod store;
+mod throttle;
+mod walk;
 
-pub use bucket::Bucket;
+pub use bucket::{Bucket, Multipart, UploadSource};
 pub use builder::Builder;
+pub use cache::{CacheConfig, CachedBucket};
 pub use client::Client;
+pub use compression::Compression;
 pub use error::*;
+pub use lock::{ObjectLock, ObjectLockMode, ObjectRetention};
+pub use object::{
+    CacheOutcome, CompletedPart, CopyConditions, CopyObjectResult, DeleteObjectError,
+    DeleteObjectsResult, DeletePrefixResult, DeletedObject, MultipartState, ObjectLength,
+    ObjectMetadata, ResponseOverrides, UploadReport,
+};
+pub use post::{PostConditions, PresignedPost};
+#[cfg(feature = "object_store")]
+pub use store::BucketStore;
+pub use walk::{Walk, WalkEntry};
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
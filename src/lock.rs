@@ -0,0 +1,311 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use md5::{Digest, Md5};
+use rusty_s3::S3Action;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::{
+    error::InternalError,
+    post::{iso8601, parse_iso8601},
+    Bucket, Error, Result, S3ErrorCode,
+};
+
+const OBJECT_LOCK_MODE_HEADER: &str = "x-amz-object-lock-mode";
+const OBJECT_LOCK_RETAIN_UNTIL_DATE_HEADER: &str = "x-amz-object-lock-retain-until-date";
+const OBJECT_LOCK_LEGAL_HOLD_HEADER: &str = "x-amz-object-lock-legal-hold";
+
+/// How strictly a retention protects an object, see
+/// [the S3 documentation](https://docs.aws.amazon.com/AmazonS3/latest/userguide/object-lock.html#object-lock-retention-modes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ObjectLockMode {
+    /// The users with the `s3:BypassGovernanceRetention` permission can still delete the object
+    /// or shorten its retention.
+    Governance,
+    /// Nobody can delete the object or shorten its retention, not even the root user.
+    Compliance,
+}
+
+impl ObjectLockMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ObjectLockMode::Governance => "GOVERNANCE",
+            ObjectLockMode::Compliance => "COMPLIANCE",
+        }
+    }
+}
+
+/// The retention of an object, which can't be deleted or overwritten before `retain_until`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectRetention {
+    pub mode: ObjectLockMode,
+    pub retain_until: OffsetDateTime,
+}
+
+/// The lock applied to an object when it's uploaded, see [`Bucket::put_object_locked`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectLock {
+    pub retention: Option<ObjectRetention>,
+    /// An object under a legal hold can't be deleted or overwritten until the hold is removed,
+    /// whatever its retention.
+    pub legal_hold: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RetentionResponse {
+    #[serde(with = "quick_xml::serde_helpers::text_content")]
+    mode: ObjectLockMode,
+    retain_until_date: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LegalHoldResponse {
+    status: String,
+}
+
+impl ObjectRetention {
+    /// The XML body of a `PutObjectRetention` request.
+    fn body(&self) -> String {
+        format!(
+            "<Retention xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><Mode>{}</Mode><RetainUntilDate>{}</RetainUntilDate></Retention>",
+            self.mode.as_str(),
+            iso8601(&self.retain_until, true)
+        )
+    }
+}
+
+/// The XML body of a `PutObjectLegalHold` request.
+fn legal_hold_body(on: bool) -> String {
+    format!(
+        "<LegalHold xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><Status>{}</Status></LegalHold>",
+        legal_hold_status(on)
+    )
+}
+
+fn legal_hold_status(on: bool) -> &'static str {
+    if on {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+impl Bucket {
+    /// Protect an object from being deleted or overwritten until `retain_until`.
+    /// The bucket must have been created with object lock enabled, otherwise an
+    /// [`Error::ObjectLockDisabled`] error is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use strois::{Builder, ObjectLockMode};
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// let retain_until = time::OffsetDateTime::now_utc() + Duration::from_secs(7 * 24 * 60 * 60);
+    /// bucket.put_object_retention("invoices/2024.pdf", ObjectLockMode::Compliance, retain_until)?;
+    ///
+    /// let retention = bucket.get_object_retention("invoices/2024.pdf")?.unwrap();
+    /// assert_eq!(retention.mode, ObjectLockMode::Compliance);
+    ///
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn put_object_retention(
+        &self,
+        path: impl AsRef<str>,
+        mode: ObjectLockMode,
+        retain_until: OffsetDateTime,
+    ) -> Result<()> {
+        let body = ObjectRetention { mode, retain_until }.body();
+        self.put_object_subresource(path.as_ref(), "retention", &body)
+    }
+
+    /// Get the retention of an object, `None` if it has none.
+    pub fn get_object_retention(&self, path: impl AsRef<str>) -> Result<Option<ObjectRetention>> {
+        let body = match self.get_object_subresource(path.as_ref(), "retention") {
+            Err(e) if e.s3_code() == Some(S3ErrorCode::NoSuchObjectLockConfiguration) => {
+                return Ok(None)
+            }
+            body => body?,
+        };
+        let retention: RetentionResponse =
+            quick_xml::de::from_str(&body).map_err(InternalError::BadS3Payload)?;
+        let retain_until = parse_iso8601(&retention.retain_until_date)
+            .ok_or_else(|| InternalError::BadS3Date(retention.retain_until_date.clone()))?;
+        Ok(Some(ObjectRetention {
+            mode: retention.mode,
+            retain_until,
+        }))
+    }
+
+    /// Place or remove a legal hold on an object. An object under a legal hold can't be deleted
+    /// or overwritten, whatever its retention, until the hold is removed.
+    /// The bucket must have been created with object lock enabled, otherwise an
+    /// [`Error::ObjectLockDisabled`] error is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use strois::Builder;
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// bucket.put_object_legal_hold("evidence.log", true)?;
+    /// assert!(bucket.get_object_legal_hold("evidence.log")?);
+    ///
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn put_object_legal_hold(&self, path: impl AsRef<str>, on: bool) -> Result<()> {
+        self.put_object_subresource(path.as_ref(), "legal-hold", &legal_hold_body(on))
+    }
+
+    /// Return `true` if the object is under a legal hold.
+    pub fn get_object_legal_hold(&self, path: impl AsRef<str>) -> Result<bool> {
+        let body = match self.get_object_subresource(path.as_ref(), "legal-hold") {
+            Err(e) if e.s3_code() == Some(S3ErrorCode::NoSuchObjectLockConfiguration) => {
+                return Ok(false)
+            }
+            body => body?,
+        };
+        let legal_hold: LegalHoldResponse =
+            quick_xml::de::from_str(&body).map_err(InternalError::BadS3Payload)?;
+        Ok(legal_hold.status == "ON")
+    }
+
+    /// Put an object already protected by a retention or a legal hold.
+    /// The bucket must have been created with object lock enabled, otherwise an
+    /// [`Error::ObjectLockDisabled`] error is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use strois::{Builder, ObjectLock, ObjectLockMode, ObjectRetention};
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// let lock = ObjectLock {
+    ///     retention: Some(ObjectRetention {
+    ///         mode: ObjectLockMode::Governance,
+    ///         retain_until: time::OffsetDateTime::now_utc() + Duration::from_secs(24 * 60 * 60),
+    ///     }),
+    ///     legal_hold: false,
+    /// };
+    /// bucket.put_object_locked("audit.log", "kero", &lock)?;
+    ///
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn put_object_locked(
+        &self,
+        path: impl AsRef<str>,
+        content: impl AsRef<[u8]>,
+        lock: &ObjectLock,
+    ) -> Result<()> {
+        let content = content.as_ref();
+        // S3 requires the MD5 of the objects uploaded with a lock.
+        let md5 = BASE64.encode(Md5::digest(content));
+        let retention = lock
+            .retention
+            .map(|retention| (retention.mode, iso8601(&retention.retain_until, true)));
+
+        let mut headers = vec![("Content-MD5", md5.as_str())];
+        if let Some((mode, retain_until)) = &retention {
+            headers.push((OBJECT_LOCK_MODE_HEADER, mode.as_str()));
+            headers.push((OBJECT_LOCK_RETAIN_UNTIL_DATE_HEADER, retain_until.as_str()));
+        }
+        if lock.legal_hold {
+            headers.push((OBJECT_LOCK_LEGAL_HOLD_HEADER, legal_hold_status(true)));
+        }
+        self.put_object_with_headers(path.as_ref(), content, &headers)
+            .map_err(|e| self.object_lock_error(e))?;
+        Ok(())
+    }
+
+    /// Send the XML `body` to the `subresource` of an object.
+    fn put_object_subresource(&self, path: &str, subresource: &str, body: &str) -> Result<()> {
+        let key = self.key(path);
+        let mut action = self.bucket.put_object(Some(&self.client.cred), &key);
+        action.query_mut().insert(subresource, "");
+        action
+            .headers_mut()
+            .insert("Content-MD5", BASE64.encode(Md5::digest(body)));
+        self.client
+            .put_with_body(action, body.as_bytes(), body.len())
+            .map_err(|e| self.object_lock_error(e))?;
+        Ok(())
+    }
+
+    /// Get the XML of the `subresource` of an object.
+    fn get_object_subresource(&self, path: &str, subresource: &str) -> Result<String> {
+        let key = self.key(path);
+        let mut action = self.bucket.get_object(Some(&self.client.cred), &key);
+        action.query_mut().insert(subresource, "");
+        let response = self
+            .client
+            .get(action)
+            .map_err(|e| self.object_lock_error(e))?;
+        Ok(response.into_string()?)
+    }
+
+    /// S3 refuses the object lock requests with a generic `InvalidRequest` error when the bucket
+    /// doesn't have object lock enabled.
+    fn object_lock_error(&self, error: Error) -> Error {
+        let missing_lock = match &error {
+            Error::S3Error(e) if e.code == S3ErrorCode::InvalidRequest => e
+                .message
+                .as_deref()
+                .is_some_and(|message| message.replace(' ', "").contains("ObjectLock")),
+            Error::S3Error(e) => e.code == S3ErrorCode::ObjectLockConfigurationNotFoundError,
+            _ => false,
+        };
+        if missing_lock {
+            Error::ObjectLockDisabled {
+                bucket: self.name().to_string(),
+            }
+        } else {
+            error
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bodies() {
+        let retention = ObjectRetention {
+            mode: ObjectLockMode::Governance,
+            retain_until: OffsetDateTime::from_unix_timestamp(1767229200).unwrap(),
+        };
+        insta::assert_display_snapshot!(retention.body(), @r###"<Retention xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Mode>GOVERNANCE</Mode><RetainUntilDate>2026-01-01T01:00:00.000Z</RetainUntilDate></Retention>"###);
+        insta::assert_display_snapshot!(legal_hold_body(true), @r###"<LegalHold xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Status>ON</Status></LegalHold>"###);
+    }
+
+    #[test]
+    fn parse_retention() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Retention xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Mode>COMPLIANCE</Mode><RetainUntilDate>2026-01-01T01:00:00.123Z</RetainUntilDate></Retention>"#;
+        let retention: RetentionResponse = quick_xml::de::from_str(body).unwrap();
+        assert_eq!(retention.mode, ObjectLockMode::Compliance);
+        assert_eq!(
+            parse_iso8601(&retention.retain_until_date),
+            OffsetDateTime::from_unix_timestamp(1767229200).ok()
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use http::header::{
+    CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, ETAG,
+    LAST_MODIFIED,
+};
+use rusty_s3::S3Action;
+use serde::Deserialize;
+use ureq::Response;
+
+use crate::{bucket::quote_etag, error::InternalError, Result, S3Error, S3ErrorCode};
+
+pub(crate) const WEBSITE_REDIRECT_LOCATION_HEADER: &str = "x-amz-website-redirect-location";
+/// The base64 encoded SHA256 of the object, only returned when the checksum mode is enabled.
+#[cfg(feature = "checksum")]
+pub(crate) const CHECKSUM_SHA256_HEADER: &str = "x-amz-checksum-sha256";
+#[cfg(feature = "checksum")]
+pub(crate) const CHECKSUM_MODE_HEADER: &str = "x-amz-checksum-mode";
+/// The user metadata storing the hex encoded SHA256 of an object.
+pub(crate) const SHA256_METADATA: &str = "sha256";
+
+/// The metadata of an object, as returned by [`Bucket::head_object`](crate::Bucket::head_object).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMetadata {
+    /// The size of the object in bytes, as it's stored: the size of the encoded content for an
+    /// object uploaded with a `Content-Encoding`.
+    pub content_length: u64,
+    /// The ETag of the object without the surrounding quotes.
+    pub etag: String,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    pub content_disposition: Option<String>,
+    /// The `Content-Encoding` the object was uploaded with, e.g. `gzip`.
+    pub content_encoding: Option<String>,
+    pub cache_control: Option<String>,
+    /// Where a website hosted on the bucket redirects the requests for this object.
+    pub website_redirect_location: Option<String>,
+    /// The user-defined metadata, the `x-amz-meta-` prefix is stripped from the keys.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ObjectMetadata {
+    pub(crate) fn from_response(response: &Response) -> Self {
+        let metadata = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let key = name
+                    .to_ascii_lowercase()
+                    .strip_prefix("x-amz-meta-")?
+                    .to_string();
+                let value = response.header(&name)?.to_string();
+                Some((key, value))
+            })
+            .collect();
+
+        ObjectMetadata {
+            content_length: response
+                .header(CONTENT_LENGTH.as_str())
+                .and_then(|length| length.parse().ok())
+                .unwrap_or_default(),
+            etag: response
+                .header(ETAG.as_str())
+                .unwrap_or_default()
+                .trim_matches('"')
+                .to_string(),
+            last_modified: response.header(LAST_MODIFIED.as_str()).map(String::from),
+            content_type: response.header(CONTENT_TYPE.as_str()).map(String::from),
+            content_disposition: response
+                .header(CONTENT_DISPOSITION.as_str())
+                .map(String::from),
+            content_encoding: response.header(CONTENT_ENCODING.as_str()).map(String::from),
+            cache_control: response.header(CACHE_CONTROL.as_str()).map(String::from),
+            website_redirect_location: response
+                .header(WEBSITE_REDIRECT_LOCATION_HEADER)
+                .map(String::from),
+            metadata,
+        }
+    }
+}
+
+impl ObjectMetadata {
+    /// The hex encoded SHA256 of the content, if the object was uploaded with
+    /// [`Bucket::put_object_with_sha256`](crate::Bucket::put_object_with_sha256).
+    /// Unlike the ETag it doesn't depend on how the object was uploaded, it can be compared
+    /// with the hash of a local file to know if it changed.
+    pub fn sha256(&self) -> Option<&str> {
+        self.metadata.get(SHA256_METADATA).map(String::as_str)
+    }
+}
+
+/// The object created by a copy, see [`Bucket::copy_object`](crate::Bucket::copy_object).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CopyObjectResult {
+    /// The ETag of the new object without the surrounding quotes.
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    pub last_modified: String,
+}
+
+impl CopyObjectResult {
+    /// S3 can fail a copy after sending the `200 OK` status, the error is then in the body.
+    pub(crate) fn parse(body: &str) -> Result<Self> {
+        match quick_xml::de::from_str::<CopyObjectResult>(body) {
+            Ok(mut result) => {
+                result.etag = result.etag.trim_matches('"').to_string();
+                Ok(result)
+            }
+            Err(e) => match S3Error::parse(body) {
+                Ok(error) => Err(error.into()),
+                Err(_) => Err(InternalError::BadS3Payload(e).into()),
+            },
+        }
+    }
+}
+
+/// Override the headers S3 sends back with an object, regardless of the metadata it was stored with.
+/// See [`Bucket::presign_get_with_overrides`](crate::Bucket::presign_get_with_overrides).
+///
+/// # Example
+/// ```
+/// use strois::ResponseOverrides;
+///
+/// let overrides = ResponseOverrides::new()
+///     .content_type("application/pdf")
+///     .attachment("report.pdf");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResponseOverrides {
+    pub content_type: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_language: Option<String>,
+    pub content_encoding: Option<String>,
+    pub cache_control: Option<String>,
+    pub expires: Option<String>,
+}
+
+impl ResponseOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn content_disposition(mut self, content_disposition: impl Into<String>) -> Self {
+        self.content_disposition = Some(content_disposition.into());
+        self
+    }
+
+    /// Make the browsers download the object as a file named `filename`.
+    pub fn attachment(self, filename: &str) -> Self {
+        self.content_disposition(format!("attachment; filename=\"{filename}\""))
+    }
+
+    pub fn content_language(mut self, content_language: impl Into<String>) -> Self {
+        self.content_language = Some(content_language.into());
+        self
+    }
+
+    pub fn content_encoding(mut self, content_encoding: impl Into<String>) -> Self {
+        self.content_encoding = Some(content_encoding.into());
+        self
+    }
+
+    pub fn cache_control(mut self, cache_control: impl Into<String>) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
+
+    pub fn expires(mut self, expires: impl Into<String>) -> Self {
+        self.expires = Some(expires.into());
+        self
+    }
+
+    /// Add the `response-*` query parameters to the action.
+    pub(crate) fn apply<'a>(&'a self, action: &mut impl S3Action<'a>) {
+        let query = action.query_mut();
+        let overrides = [
+            ("response-content-type", &self.content_type),
+            ("response-content-disposition", &self.content_disposition),
+            ("response-content-language", &self.content_language),
+            ("response-content-encoding", &self.content_encoding),
+            ("response-cache-control", &self.cache_control),
+            ("response-expires", &self.expires),
+        ];
+        for (name, value) in overrides {
+            if let Some(value) = value {
+                query.insert(name, value.as_str());
+            }
+        }
+    }
+}
+
+/// The conditions the source object must meet to be copied.
+/// See [`Bucket::copy_object_if`](crate::Bucket::copy_object_if).
+///
+/// # Example
+/// ```
+/// use strois::CopyConditions;
+///
+/// // Only copy the object if it's still the one we validated.
+/// let conditions = CopyConditions::new().if_match("d41d8cd98f00b204e9800998ecf8427e");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CopyConditions {
+    pub if_match: Option<String>,
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<SystemTime>,
+    pub if_unmodified_since: Option<SystemTime>,
+}
+
+impl CopyConditions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy the object only if its ETag is `etag`.
+    pub fn if_match(mut self, etag: impl Into<String>) -> Self {
+        self.if_match = Some(etag.into());
+        self
+    }
+
+    /// Copy the object only if its ETag isn't `etag`.
+    pub fn if_none_match(mut self, etag: impl Into<String>) -> Self {
+        self.if_none_match = Some(etag.into());
+        self
+    }
+
+    /// Copy the object only if it was modified after `date`.
+    pub fn if_modified_since(mut self, date: SystemTime) -> Self {
+        self.if_modified_since = Some(date);
+        self
+    }
+
+    /// Copy the object only if it wasn't modified after `date`.
+    pub fn if_unmodified_since(mut self, date: SystemTime) -> Self {
+        self.if_unmodified_since = Some(date);
+        self
+    }
+
+    /// The `x-amz-copy-source-if-*` headers of the conditions.
+    pub(crate) fn headers(&self) -> Vec<(String, String)> {
+        let etags = [
+            ("x-amz-copy-source-if-match", &self.if_match),
+            ("x-amz-copy-source-if-none-match", &self.if_none_match),
+        ];
+        let dates = [
+            (
+                "x-amz-copy-source-if-modified-since",
+                &self.if_modified_since,
+            ),
+            (
+                "x-amz-copy-source-if-unmodified-since",
+                &self.if_unmodified_since,
+            ),
+        ];
+
+        let etags = etags
+            .into_iter()
+            .filter_map(|(name, etag)| Some((name.to_string(), quote_etag(etag.as_ref()?))));
+        let dates = dates
+            .into_iter()
+            .filter_map(|(name, date)| Some((name.to_string(), httpdate::fmt_http_date((*date)?))));
+        etags.chain(dates).collect()
+    }
+}
+
+/// The result of [`Bucket::get_object_cached`](crate::Bucket::get_object_cached).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheOutcome {
+    /// The object still has the known ETag, it wasn't downloaded again.
+    Unchanged,
+    /// The object was modified or no ETag was known.
+    Updated {
+        bytes: Vec<u8>,
+        /// The ETag of the object without the surrounding quotes, to send on the next call.
+        /// `None` if the server doesn't return ETags.
+        etag: Option<String>,
+    },
+}
+
+/// How an object was uploaded by [`Bucket::put_smart`](crate::Bucket::put_smart).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadReport {
+    /// The size of the parts in bytes, only the last one can be smaller.
+    pub part_size: usize,
+    /// The number of parts sent, `1` when the object was sent in a single request, even when
+    /// it's empty.
+    pub part_count: usize,
+}
+
+/// The lengths of an object read by
+/// [`Bucket::get_object_reader_decompressed_with_length`](crate::Bucket::get_object_reader_decompressed_with_length).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectLength {
+    /// The size of the object as stored on S3 when it's decompressed while being read.
+    /// It's the number of bytes received, not the number of bytes yielded by the reader.
+    pub compressed: Option<u64>,
+    /// The number of bytes yielded by the reader, only known when the object isn't compressed:
+    /// gzip and zstd don't reliably store the size of the decompressed content.
+    pub decompressed: Option<u64>,
+}
+
+/// The result of a batch delete.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectsResult {
+    /// The keys that were deleted. Always empty in quiet mode since S3 only reports the errors.
+    #[serde(default, rename = "Deleted")]
+    pub deleted: Vec<DeletedObject>,
+    /// The keys that could not be deleted, they can be sent again.
+    #[serde(default, rename = "Error")]
+    pub errors: Vec<DeleteObjectError>,
+}
+
+impl DeleteObjectsResult {
+    /// Return `true` if every key was deleted.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub(crate) fn extend(&mut self, other: DeleteObjectsResult) {
+        self.deleted.extend(other.deleted);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// The result of [`Bucket::delete_prefix`](crate::Bucket::delete_prefix).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeletePrefixResult {
+    /// The number of objects deleted.
+    pub deleted: usize,
+    /// The keys that could not be deleted, they can be sent again.
+    pub errors: Vec<DeleteObjectError>,
+}
+
+impl DeletePrefixResult {
+    /// Return `true` if every key was deleted.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeletedObject {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectError {
+    pub key: String,
+    #[serde(with = "quick_xml::serde_helpers::text_content")]
+    pub code: S3ErrorCode,
+    pub message: Option<String>,
+}
+
+/// Everything needed to resume a multipart upload, even from another process, see
+/// [`Multipart::state`](crate::Multipart::state) and
+/// [`Bucket::resume_multipart`](crate::Bucket::resume_multipart).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultipartState {
+    pub upload_id: String,
+    /// The key of the object, including the prefix of the bucket.
+    pub path: String,
+    /// The parts already uploaded, numbered from `1` without gaps.
+    pub parts: Vec<CompletedPart>,
+    /// The size of the first part, `None` until a part is uploaded. When all the parts but the
+    /// last one have the same size, the upload resumes at `parts.len() * part_size` bytes.
+    pub part_size: Option<usize>,
+    /// The size of the last part if it's smaller than 5MiB, S3 refuses any part after it.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub short_part: Option<usize>,
+}
+
+/// A part of a multipart upload accepted by S3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompletedPart {
+    pub number: u16,
+    /// The ETag of the part without the surrounding quotes.
+    pub etag: String,
+}
@@ -0,0 +1,45 @@
+use std::sync::{mpsc, Arc, Mutex};
+
+/// Apply `f` on every item with up to `concurrency` threads.
+/// The results are yielded as soon as they're available, thus they're not ordered.
+/// If the returned iterator is dropped, the threads stop after their current item.
+pub(crate) fn parallel<T, R, F>(
+    items: impl IntoIterator<Item = T>,
+    concurrency: usize,
+    f: F,
+) -> mpsc::IntoIter<(T, R)>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(&T) -> R + Send + Sync + 'static,
+{
+    let (items_sender, items_receiver) = mpsc::channel();
+    for item in items {
+        // can't fail, we still own the receiver
+        let _ = items_sender.send(item);
+    }
+    drop(items_sender);
+
+    let items = Arc::new(Mutex::new(items_receiver));
+    let f = Arc::new(f);
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..concurrency.max(1) {
+        let items = items.clone();
+        let f = f.clone();
+        let sender = sender.clone();
+        std::thread::spawn(move || loop {
+            // the lock is released before processing the item
+            let item = match items.lock().unwrap().recv() {
+                Ok(item) => item,
+                Err(_) => return,
+            };
+            let result = f(&item);
+            if sender.send((item, result)).is_err() {
+                return;
+            }
+        });
+    }
+
+    receiver.into_iter()
+}
@@ -0,0 +1,294 @@
+use std::{fmt::Write, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::Bucket;
+
+/// The restrictions a browser upload must respect.
+/// See [`Bucket::presign_post`].
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use strois::PostConditions;
+///
+/// // Only accept images of at most 10MiB for the next 10 minutes.
+/// let conditions = PostConditions::new()
+///     .expires_in(Duration::from_secs(10 * 60))
+///     .content_type_starts_with("image/")
+///     .content_length_range(0, 10 * 1024 * 1024);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostConditions {
+    /// How long the form can be submitted. One hour by default.
+    pub expires_in: Duration,
+    /// The minimum and maximum size in bytes of the uploaded file.
+    pub content_length_range: Option<(u64, u64)>,
+    /// The exact `Content-Type` of the uploaded file, it's sent as a hidden field of the form.
+    pub content_type: Option<String>,
+    /// A prefix the `Content-Type` of the uploaded file must start with.
+    /// The form must then contain a `Content-Type` field.
+    pub content_type_starts_with: Option<String>,
+}
+
+impl Default for PostConditions {
+    fn default() -> Self {
+        Self {
+            expires_in: Duration::from_secs(60 * 60),
+            content_length_range: None,
+            content_type: None,
+            content_type_starts_with: None,
+        }
+    }
+}
+
+impl PostConditions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn expires_in(mut self, expires_in: Duration) -> Self {
+        self.expires_in = expires_in;
+        self
+    }
+
+    pub fn content_length_range(mut self, min: u64, max: u64) -> Self {
+        self.content_length_range = Some((min, max));
+        self
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn content_type_starts_with(mut self, prefix: impl Into<String>) -> Self {
+        self.content_type_starts_with = Some(prefix.into());
+        self
+    }
+
+    /// The JSON policy restricting the upload to `key` in `bucket`, the `fields` of the form
+    /// must all be part of it.
+    fn policy(
+        &self,
+        bucket: &str,
+        key: &str,
+        fields: &[(&str, String)],
+        expiration: &OffsetDateTime,
+    ) -> String {
+        let mut conditions = vec![
+            format!("{{\"bucket\":{}}}", json_string(bucket)),
+            format!("{{\"key\":{}}}", json_string(key)),
+        ];
+        conditions.extend(
+            fields
+                .iter()
+                .map(|(name, value)| format!("{{{}:{}}}", json_string(name), json_string(value))),
+        );
+        if let Some((min, max)) = self.content_length_range {
+            conditions.push(format!("[\"content-length-range\",{min},{max}]"));
+        }
+        if let Some(prefix) = &self.content_type_starts_with {
+            conditions.push(format!(
+                "[\"starts-with\",\"$Content-Type\",{}]",
+                json_string(prefix)
+            ));
+        }
+
+        format!(
+            "{{\"expiration\":\"{}\",\"conditions\":[{}]}}",
+            iso8601(expiration, true),
+            conditions.join(",")
+        )
+    }
+}
+
+/// Everything a browser needs to upload a file directly to S3 with an HTML form.
+/// See [`Bucket::presign_post`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignedPost {
+    /// The `action` of the form.
+    pub url: Url,
+    /// The hidden fields of the form, they must all be sent before the file, which must be the
+    /// last field of the form and be named `file`.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Bucket {
+    /// Presign an upload of `key` made by a browser with an HTML form, see
+    /// [the S3 documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-HTTPPOSTForms.html).
+    /// Unlike a presigned URL, the size and the type of the file are enforced by S3
+    /// through the `conditions`.
+    ///
+    /// The form must be sent with `enctype="multipart/form-data"` to [`PresignedPost::url`],
+    /// contain all the [`PresignedPost::fields`] and end with the file.
+    ///
+    /// # Example
+    /// ```
+    /// use strois::{Builder, PostConditions};
+    ///
+    /// let bucket = Builder::new("http://localhost:9000")?
+    ///     .key("minioadmin")
+    ///     .secret("minioadmin")
+    ///     .with_url_path_style(true)
+    ///     .bucket("tamo")?
+    ///     .get_or_create()?;
+    ///
+    /// let conditions = PostConditions::new().content_length_range(0, 1024 * 1024);
+    /// let post = bucket.presign_post("avatars/kero.png", &conditions)?;
+    ///
+    /// let mut form = format!("<form action=\"{}\" method=\"post\" enctype=\"multipart/form-data\">", post.url);
+    /// for (name, value) in &post.fields {
+    ///     form += &format!("<input type=\"hidden\" name=\"{name}\" value=\"{value}\"/>");
+    /// }
+    /// form += "<input type=\"file\" name=\"file\"/><input type=\"submit\"/></form>";
+    ///
+    /// # Ok::<(), strois::Error>(())
+    /// ```
+    pub fn presign_post(
+        &self,
+        key: impl AsRef<str>,
+        conditions: &PostConditions,
+    ) -> crate::Result<PresignedPost> {
+        let key = key.as_ref();
+        self.validate_key(key)?;
+        let key = self.key(key);
+
+        let cred = &self.client.cred;
+        let now = self.client.now();
+        let region = self.bucket.region();
+        let date = iso8601(&now, false);
+        let credential = format!("{}/{}/{region}/s3/aws4_request", cred.key(), &date[..8]);
+
+        let mut fields = Vec::new();
+        if let Some(content_type) = &conditions.content_type {
+            fields.push(("Content-Type", content_type.clone()));
+        }
+        if self.client.request_payer {
+            fields.push(("x-amz-request-payer", "requester".to_string()));
+        }
+        fields.push(("x-amz-algorithm", "AWS4-HMAC-SHA256".to_string()));
+        fields.push(("x-amz-credential", credential));
+        fields.push(("x-amz-date", date.clone()));
+        if let Some(token) = cred.token() {
+            fields.push(("x-amz-security-token", token.to_string()));
+        }
+
+        let expiration = now + conditions.expires_in;
+        let policy = conditions.policy(self.bucket.name(), &key, &fields, &expiration);
+        let policy = BASE64.encode(policy);
+        let signature = signature(cred.secret(), &date[..8], region, &policy);
+
+        let fields = [("key", key.to_string())]
+            .into_iter()
+            .chain(fields)
+            .chain([("policy", policy), ("x-amz-signature", signature)])
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        Ok(PresignedPost {
+            url: self.bucket.base_url().clone(),
+            fields,
+        })
+    }
+}
+
+/// Sign the base64 encoded policy with the SigV4 signing key of the day.
+fn signature(secret: &str, yyyymmdd: &str, region: &str, policy: &str) -> String {
+    let key = [yyyymmdd, region, "s3", "aws4_request"]
+        .into_iter()
+        .fold(format!("AWS4{secret}").into_bytes(), |key, data| {
+            hmac_sha256(&key, data)
+        });
+    hmac_sha256(&key, policy)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Format the date as `20130524T000000Z`, or as `2013-05-24T00:00:00.000Z` for the expiration
+/// of a policy.
+pub(crate) fn iso8601(date: &OffsetDateTime, extended: bool) -> String {
+    let date = date.to_offset(time::UtcOffset::UTC);
+    let (year, month, day) = (date.year(), date.month() as u8, date.day());
+    let (hour, minute, second) = (date.hour(), date.minute(), date.second());
+    if extended {
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.000Z")
+    } else {
+        format!("{year:04}{month:02}{day:02}T{hour:02}{minute:02}{second:02}Z")
+    }
+}
+
+/// Parse a date formatted as `2013-05-24T00:00:00.000Z`, the milliseconds are ignored.
+pub(crate) fn parse_iso8601(date: &str) -> Option<OffsetDateTime> {
+    let (date, time) = date.strip_suffix('Z')?.split_once('T')?;
+    let time = time.split('.').next()?;
+
+    let mut date = date.splitn(3, '-');
+    let year = date.next()?.parse().ok()?;
+    let month: u8 = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+    let mut time = time.splitn(3, ':').map(|n| n.parse().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+
+    let date = time::Date::from_calendar_date(year, month.try_into().ok()?, day).ok()?;
+    Some(date.with_hms(hour, minute, second).ok()?.assume_utc())
+}
+
+fn json_string(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(ret, "\\u{:04x}", c as u32);
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn policy() {
+        let conditions = PostConditions::new()
+            .content_length_range(1, 1024)
+            .content_type_starts_with("image/");
+        let fields = [("x-amz-date", "20260101T000000Z".to_string())];
+        let policy = conditions.policy(
+            "tamo",
+            "kero \"the\" frog\n",
+            &fields,
+            &OffsetDateTime::from_unix_timestamp(1767229200).unwrap(),
+        );
+        insta::assert_display_snapshot!(policy, @r###"{"expiration":"2026-01-01T01:00:00.000Z","conditions":[{"bucket":"tamo"},{"key":"kero \"the\" frog\u000a"},{"x-amz-date":"20260101T000000Z"},["content-length-range",1,1024],["starts-with","$Content-Type","image/"]]}"###);
+    }
+
+    #[test]
+    fn parse_iso8601() {
+        let date = OffsetDateTime::from_unix_timestamp(1767229200).unwrap();
+        assert_eq!(super::parse_iso8601(&iso8601(&date, true)), Some(date));
+        assert_eq!(super::parse_iso8601("2026-01-01T01:00:00Z"), Some(date));
+        assert_eq!(super::parse_iso8601("2026-13-01T01:00:00Z"), None);
+        assert_eq!(super::parse_iso8601("20260101T010000Z"), None);
+    }
+}
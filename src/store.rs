@@ -0,0 +1,598 @@
+use std::{fmt, io::Read, ops::Range};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use http::header::{
+    CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_MATCH, IF_NONE_MATCH, LAST_MODIFIED, RANGE,
+};
+use object_store::{
+    path::Path, GetOptions, GetResult, GetResultPayload, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutMode, PutOptions, PutResult,
+};
+use rusty_s3::{actions::list_objects_v2::ListObjectsContent, S3Action};
+use tokio::io::AsyncWrite;
+use ureq::Response;
+
+use crate::{Bucket, Error, S3ErrorCode};
+
+/// The size of the chunks sent in the streams returned by [`BucketStore::get_opts`].
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An [`ObjectStore`] backed by a strois [`Bucket`].
+///
+/// Since strois is synchronous, every request is executed on the blocking thread pool
+/// of tokio. Only the core methods are supported: `get`, `put`, `list`, `delete` and `head`.
+///
+/// # Example
+/// ```
+/// use strois::{Builder, BucketStore};
+/// use object_store::ObjectStore;
+///
+/// let bucket = Builder::new("http://localhost:9000")?
+///     .key("minioadmin")
+///     .secret("minioadmin")
+///     .with_url_path_style(true)
+///     .bucket("tamo")?
+///     .get_or_create()?;
+///
+/// let store = BucketStore::new(bucket);
+/// # Ok::<(), strois::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BucketStore {
+    bucket: Bucket,
+}
+
+impl BucketStore {
+    pub fn new(bucket: Bucket) -> Self {
+        Self { bucket }
+    }
+
+    /// Return the underlying [`Bucket`].
+    pub fn bucket(&self) -> &Bucket {
+        &self.bucket
+    }
+
+    /// Run a strois operation on the blocking thread pool of tokio.
+    async fn run<T, F>(&self, location: &Path, f: F) -> object_store::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Bucket, &str) -> crate::Result<T> + Send + 'static,
+    {
+        let bucket = self.bucket.clone();
+        let path = location.to_string();
+        tokio::task::spawn_blocking(move || {
+            f(&bucket, &path).map_err(|e| into_store_error(&path, e))
+        })
+        .await?
+    }
+}
+
+impl From<Bucket> for BucketStore {
+    fn from(bucket: Bucket) -> Self {
+        Self::new(bucket)
+    }
+}
+
+impl fmt::Display for BucketStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "strois({})", self.bucket.name())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for BucketStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        if opts.mode != PutMode::Overwrite {
+            return Err(object_store::Error::NotImplemented);
+        }
+
+        self.run(location, move |bucket, path| {
+            let response = bucket.put_object_with_headers(path, &bytes, &[])?;
+            Ok(PutResult {
+                e_tag: response.header(ETAG.as_str()).map(String::from),
+                version: None,
+            })
+        })
+        .await
+    }
+
+    async fn put_multipart(
+        &self,
+        _location: &Path,
+    ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn abort_multipart(
+        &self,
+        _location: &Path,
+        _multipart_id: &MultipartId,
+    ) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        if options.version.is_some()
+            || options.if_modified_since.is_some()
+            || options.if_unmodified_since.is_some()
+        {
+            return Err(object_store::Error::NotImplemented);
+        }
+
+        let head = options.head;
+        let response = self
+            .run(location, move |bucket, path| {
+                let key = bucket.key(path);
+                let response = if head {
+                    let mut action = bucket.bucket.head_object(Some(&bucket.client.cred), &key);
+                    set_get_options(&mut action, &options);
+                    bucket.client.head(action)?
+                } else {
+                    let mut action = bucket.bucket.get_object(Some(&bucket.client.cred), &key);
+                    set_get_options(&mut action, &options);
+                    bucket.client.get(action)?
+                };
+                Ok(response)
+            })
+            .await?;
+
+        if response.status() == 304 {
+            return Err(object_store::Error::NotModified {
+                path: location.to_string(),
+                source: "the object was not modified".into(),
+            });
+        }
+        let (meta, range) = parse_meta(location.clone(), &response)?;
+
+        let payload = if head {
+            futures::stream::empty().boxed()
+        } else {
+            let path = location.to_string();
+            let mut reader = self.bucket.client.reader(response);
+            stream_blocking(move |sender| loop {
+                let mut buffer = vec![0; CHUNK_SIZE];
+                let chunk = match reader.read(&mut buffer) {
+                    Ok(0) => return,
+                    Ok(read) => {
+                        buffer.truncate(read);
+                        Ok(Bytes::from(buffer))
+                    }
+                    Err(e) => Err(into_store_error(&path, e.into())),
+                };
+                let stop = chunk.is_err();
+                if sender.blocking_send(chunk).is_err() || stop {
+                    return;
+                }
+            })
+        };
+
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(payload),
+            meta,
+            range,
+        })
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.run(location, |bucket, path| bucket.delete_object(path))
+            .await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        let bucket = self.bucket.clone();
+        let prefix = prefix
+            .map(|prefix| format!("{prefix}/"))
+            .unwrap_or_default();
+
+        stream_blocking(move |sender| {
+            let objects = match bucket.list_objects(&prefix) {
+                Ok(objects) => objects,
+                Err(e) => {
+                    let _ = sender.blocking_send(Err(into_store_error(&prefix, e)));
+                    return;
+                }
+            };
+
+            for object in objects {
+                let object = object
+                    .map_err(|e| into_store_error(&prefix, e))
+                    .and_then(into_object_meta);
+                let stop = object.is_err();
+                if sender.blocking_send(object).is_err() || stop {
+                    return;
+                }
+            }
+        })
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        _prefix: Option<&Path>,
+    ) -> object_store::Result<ListResult> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn copy(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+}
+
+/// Run the producer on the blocking thread pool of tokio and stream what it sends.
+/// The producer is only spawned on the first poll of the stream, thus the stream can be
+/// created outside of a tokio runtime.
+fn stream_blocking<T, F>(producer: F) -> BoxStream<'static, T>
+where
+    T: Send + 'static,
+    F: FnOnce(tokio::sync::mpsc::Sender<T>) + Send + 'static,
+{
+    futures::stream::once(async move {
+        let (sender, receiver) = tokio::sync::mpsc::channel(16);
+        tokio::task::spawn_blocking(move || producer(sender));
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+    })
+    .flatten()
+    .boxed()
+}
+
+fn set_get_options<'a>(action: &mut impl S3Action<'a>, options: &GetOptions) {
+    let headers = action.headers_mut();
+    if let Some(range) = &options.range {
+        headers.insert(RANGE.as_str(), range.to_string());
+    }
+    if let Some(etag) = &options.if_match {
+        headers.insert(IF_MATCH.as_str(), etag.clone());
+    }
+    if let Some(etag) = &options.if_none_match {
+        headers.insert(IF_NONE_MATCH.as_str(), etag.clone());
+    }
+}
+
+fn parse_meta(
+    location: Path,
+    response: &Response,
+) -> object_store::Result<(ObjectMeta, Range<usize>)> {
+    let length = response
+        .header(CONTENT_LENGTH.as_str())
+        .and_then(|length| length.parse().ok())
+        .unwrap_or_default();
+    // `Content-Range: bytes 0-99/1234`
+    let (range, size) = match response
+        .header(CONTENT_RANGE.as_str())
+        .and_then(|range| range.strip_prefix("bytes "))
+        .and_then(|range| range.split_once('/'))
+        .and_then(|(range, size)| Some((range.split_once('-')?, size.parse().ok()?)))
+    {
+        Some(((start, end), size)) => {
+            let start = start.parse().unwrap_or_default();
+            let end = end.parse().map_or(size, |end: usize| end + 1);
+            (start..end, size)
+        }
+        None => (0..length, length),
+    };
+    let last_modified = response
+        .header(LAST_MODIFIED.as_str())
+        .ok_or_else(|| invalid_last_modified(&location, None))?;
+    let last_modified = DateTime::parse_from_rfc2822(last_modified)
+        .map_err(|_| invalid_last_modified(&location, Some(last_modified)))?
+        .with_timezone(&Utc);
+
+    let meta = ObjectMeta {
+        location,
+        last_modified,
+        size,
+        e_tag: response.header(ETAG.as_str()).map(String::from),
+        version: None,
+    };
+    Ok((meta, range))
+}
+
+fn into_object_meta(object: ListObjectsContent) -> object_store::Result<ObjectMeta> {
+    let location = Path::parse(&object.key)?;
+    let last_modified = DateTime::parse_from_rfc3339(&object.last_modified)
+        .map_err(|_| invalid_last_modified(&location, Some(&object.last_modified)))?
+        .with_timezone(&Utc);
+    Ok(ObjectMeta {
+        location,
+        last_modified,
+        size: object.size as usize,
+        e_tag: Some(object.etag),
+        version: None,
+    })
+}
+
+/// A `Last-Modified` date that can't be parsed, rather than pretending the object dates from 1970.
+fn invalid_last_modified(location: &Path, date: Option<&str>) -> object_store::Error {
+    let message = match date {
+        Some(date) => format!("The last modified date of `{location}` is invalid: `{date}`."),
+        None => format!("The last modified date of `{location}` is missing."),
+    };
+    object_store::Error::Generic {
+        store: "strois",
+        source: message.into(),
+    }
+}
+
+fn into_store_error(path: &str, error: Error) -> object_store::Error {
+    match error.s3_code() {
+        Some(S3ErrorCode::NoSuchKey) => object_store::Error::NotFound {
+            path: path.to_string(),
+            source: Box::new(error),
+        },
+        Some(S3ErrorCode::PreconditionFailed) => object_store::Error::Precondition {
+            path: path.to_string(),
+            source: Box::new(error),
+        },
+        _ => object_store::Error::Generic {
+            store: "strois",
+            source: Box::new(error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+
+    use futures::TryStreamExt;
+    use object_store::GetRange;
+
+    use super::*;
+    use crate::bucket::test::{fake_server, Reply};
+    use crate::Client;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn store(addr: std::net::SocketAddr) -> BucketStore {
+        let client = Client::builder(format!("http://{addr}"))
+            .unwrap()
+            .key("minioadmin")
+            .secret("minioadmin")
+            .with_url_path_style(true)
+            .retries(0)
+            .client();
+        BucketStore::new(client.bucket("tamo").unwrap())
+    }
+
+    /// Serve the object `tamo/kero` containing `kero`, with its ranges.
+    fn object_server() -> std::net::SocketAddr {
+        let (addr, _) = fake_server(|request| {
+            let request = request.to_lowercase();
+            if request.starts_with("put") {
+                return Reply::new("200 OK", "").header("ETag", "\"kero\"");
+            }
+            let reply = if request.contains("range: bytes=1-2") {
+                Reply::new("206 Partial Content", "er").header("Content-Range", "bytes 1-2/4")
+            } else {
+                Reply::new("200 OK", "kero")
+            };
+            reply
+                .header("ETag", "\"kero\"")
+                .header("Last-Modified", "Thu, 01 Jan 2026 00:00:00 GMT")
+        });
+        addr
+    }
+
+    #[test]
+    fn put_get_head() {
+        let store = store(object_server());
+        let location = Path::from("tamo/kero");
+        block_on(async {
+            let ret = store.put(&location, Bytes::from("kero")).await.unwrap();
+            assert_eq!(ret.e_tag.as_deref(), Some("\"kero\""));
+
+            let ret = store.get(&location).await.unwrap();
+            assert_eq!(ret.range, 0..4);
+            assert_eq!(ret.meta.size, 4);
+            assert_eq!(ret.meta.e_tag.as_deref(), Some("\"kero\""));
+            assert_eq!(
+                ret.meta.last_modified.to_rfc3339(),
+                "2026-01-01T00:00:00+00:00"
+            );
+            assert_eq!(ret.bytes().await.unwrap(), "kero");
+
+            let ret = store.get_range(&location, 1..3).await.unwrap();
+            assert_eq!(ret, "er");
+            let options = GetOptions {
+                range: Some(GetRange::Bounded(1..3)),
+                ..Default::default()
+            };
+            let ret = store.get_opts(&location, options).await.unwrap();
+            assert_eq!(ret.range, 1..3);
+            assert_eq!(ret.meta.size, 4);
+
+            let meta = store.head(&location).await.unwrap();
+            assert_eq!(meta.location, location);
+            assert_eq!(meta.size, 4);
+        });
+    }
+
+    #[test]
+    fn errors() {
+        let (addr, requests) = fake_server(|request| {
+            let request = request.to_lowercase();
+            if request.contains("if-none-match") {
+                Reply::new("304 Not Modified", "")
+            } else if request.contains("if-match") {
+                let body = "<Error><Code>PreconditionFailed</Code><Message>At least one of the pre-conditions you specified did not hold</Message></Error>";
+                Reply::new("412 Precondition Failed", body)
+            } else {
+                let body = "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>";
+                Reply::new("404 Not Found", body)
+            }
+        });
+        let store = store(addr);
+        let location = Path::from("tamo");
+        block_on(async {
+            let ret = store.get(&location).await.unwrap_err();
+            assert!(
+                matches!(ret, object_store::Error::NotFound { .. }),
+                "{ret:?}"
+            );
+
+            let options = GetOptions {
+                if_match: Some("\"kero\"".to_string()),
+                ..Default::default()
+            };
+            let ret = store.get_opts(&location, options).await.unwrap_err();
+            assert!(
+                matches!(ret, object_store::Error::Precondition { .. }),
+                "{ret:?}"
+            );
+
+            let options = GetOptions {
+                if_none_match: Some("\"kero\"".to_string()),
+                ..Default::default()
+            };
+            let ret = store.get_opts(&location, options).await.unwrap_err();
+            assert!(
+                matches!(ret, object_store::Error::NotModified { .. }),
+                "{ret:?}"
+            );
+        });
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn not_implemented() {
+        let (addr, requests) = fake_server(|_| Reply::new("200 OK", ""));
+        let store = store(addr);
+        let location = Path::from("tamo");
+        block_on(async {
+            let options = PutOptions {
+                mode: PutMode::Create,
+                ..Default::default()
+            };
+            let ret = store
+                .put_opts(&location, Bytes::from("kero"), options)
+                .await;
+            assert!(matches!(ret, Err(object_store::Error::NotImplemented)));
+            let options = GetOptions {
+                if_modified_since: Some(Utc::now()),
+                ..Default::default()
+            };
+            let ret = store.get_opts(&location, options).await;
+            assert!(matches!(ret, Err(object_store::Error::NotImplemented)));
+            let ret = store.copy(&location, &Path::from("kero")).await;
+            assert!(matches!(ret, Err(object_store::Error::NotImplemented)));
+        });
+        // The unsupported options are rejected before sending anything.
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn list() {
+        let (addr, requests) = fake_server(|request| {
+            assert!(request.contains("prefix=logs%2F"), "{request}");
+            let mut body =
+                String::from("<ListBucketResult><Name>tamo</Name><MaxKeys>1000</MaxKeys>");
+            for key in ["logs/kero", "logs/tamo"] {
+                body += &format!("<Contents><Key>{key}</Key><LastModified>2026-01-01T00:00:00.000Z</LastModified><ETag>&quot;d41d8cd98f00b204e9800998ecf8427e&quot;</ETag><Size>4</Size><StorageClass>STANDARD</StorageClass></Contents>");
+            }
+            body += "</ListBucketResult>";
+            Reply::new("200 OK", body)
+        });
+        let store = store(addr);
+        // Nothing is sent before the stream is polled, even outside of a runtime.
+        let stream = store.list(Some(&Path::from("logs")));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
+        let objects: Vec<ObjectMeta> = block_on(stream.try_collect()).unwrap();
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let locations: Vec<_> = objects
+            .iter()
+            .map(|object| object.location.as_ref())
+            .collect();
+        assert_eq!(locations, ["logs/kero", "logs/tamo"]);
+        assert!(objects.iter().all(|object| object.size == 4));
+        assert_eq!(
+            objects[0].last_modified.to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            objects[0].e_tag.as_deref(),
+            Some("\"d41d8cd98f00b204e9800998ecf8427e\"")
+        );
+    }
+
+    #[test]
+    fn list_error() {
+        let (addr, _) = fake_server(|_| {
+            let body = "<Error><Code>AccessDenied</Code><Message>Access Denied.</Message></Error>";
+            Reply::new("403 Forbidden", body)
+        });
+        let store = store(addr);
+        let ret = block_on(store.list(None).try_collect::<Vec<_>>()).unwrap_err();
+        assert!(
+            matches!(
+                ret,
+                object_store::Error::Generic {
+                    store: "strois",
+                    ..
+                }
+            ),
+            "{ret:?}"
+        );
+    }
+
+    #[test]
+    fn invalid_last_modified() {
+        let (addr, _) = fake_server(|request| {
+            if request.contains("list-type=2") {
+                let body = "<ListBucketResult><Name>tamo</Name><MaxKeys>1000</MaxKeys><Contents><Key>kero</Key><LastModified>yesterday</LastModified><ETag>&quot;kero&quot;</ETag><Size>4</Size><StorageClass>STANDARD</StorageClass></Contents></ListBucketResult>";
+                Reply::new("200 OK", body)
+            } else {
+                Reply::new("200 OK", "kero").header("ETag", "\"kero\"")
+            }
+        });
+        let store = store(addr);
+        block_on(async {
+            let ret = store.get(&Path::from("kero")).await.unwrap_err();
+            insta::assert_display_snapshot!(ret, @"Generic strois error: The last modified date of `kero` is missing.");
+            let ret = store.list(None).try_collect::<Vec<_>>().await.unwrap_err();
+            insta::assert_display_snapshot!(ret, @"Generic strois error: The last modified date of `kero` is invalid: `yesterday`.");
+        });
+    }
+
+    #[test]
+    fn put_validates_keys() {
+        let (addr, requests) = fake_server(|_| Reply::new("200 OK", ""));
+        let client = Client::builder(format!("http://{addr}"))
+            .unwrap()
+            .key("minioadmin")
+            .secret("minioadmin")
+            .with_url_path_style(true)
+            .validate_keys(true)
+            .client();
+        let store = BucketStore::new(client.bucket("tamo").unwrap());
+        let location = Path::from("k".repeat(1025));
+        let ret = block_on(store.put(&location, Bytes::from("kero"))).unwrap_err();
+        assert!(
+            matches!(ret, object_store::Error::Generic { .. }),
+            "{ret:?}"
+        );
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::Read,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A token bucket shared by all the transfers of a client, see [`Builder::max_bytes_per_sec`](crate::Builder::max_bytes_per_sec).
+pub(crate) struct Throttle {
+    bytes_per_sec: u64,
+    state: Mutex<State>,
+}
+
+struct State {
+    /// The number of bytes that can be transferred right away, negative when the transfers are
+    /// ahead of the rate.
+    available: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        Throttle {
+            bytes_per_sec,
+            state: Mutex::new(State {
+                available: bytes_per_sec as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Record the transfer of `bytes` and wait until the rate is respected again.
+    fn consume(&self, bytes: usize) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let rate = self.bytes_per_sec as f64;
+            let refill = now.duration_since(state.last_refill).as_secs_f64() * rate;
+            // At most one second of transfer can be done in a burst.
+            state.available = (state.available + refill).min(rate) - bytes as f64;
+            state.last_refill = now;
+            if state.available < 0.0 {
+                Duration::from_secs_f64(-state.available / rate)
+            } else {
+                Duration::ZERO
+            }
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// A reader going through the throttle of the client, if there is one.
+pub(crate) struct ThrottledReader<R> {
+    reader: R,
+    throttle: Option<Arc<Throttle>>,
+}
+
+impl<R> ThrottledReader<R> {
+    pub(crate) fn new(reader: R, throttle: Option<Arc<Throttle>>) -> Self {
+        ThrottledReader { reader, throttle }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let throttle = match &self.throttle {
+            Some(throttle) => throttle,
+            None => return self.reader.read(buf),
+        };
+        // Don't read more than a second of transfer at once to keep the rate smooth.
+        let len = buf.len().min(throttle.bytes_per_sec as usize);
+        let read = self.reader.read(&mut buf[..len])?;
+        throttle.consume(read);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn throttled_reader() {
+        let throttle = Arc::new(Throttle::new(1000));
+        let mut reader = ThrottledReader::new(&[0; 3000][..], Some(throttle));
+
+        let now = Instant::now();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content.len(), 3000);
+        // The first second is sent as a burst, the two others are throttled.
+        let elapsed = now.elapsed();
+        assert!(elapsed >= Duration::from_millis(1900), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(3), "{elapsed:?}");
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use rusty_s3::{actions::list_objects_v2::ListObjectsContent, S3Action};
+
+use crate::{bucket::parse_list_response, Bucket, Result};
+
+/// An entry yielded by [`Bucket::walk`].
+#[derive(Debug, Clone)]
+pub enum WalkEntry {
+    /// An object.
+    Object(ListObjectsContent),
+    /// A common prefix, ending with the delimiter, whose content is yielded right after it.
+    Dir(String),
+}
+
+impl WalkEntry {
+    /// The key of the object or the prefix of the directory.
+    pub fn key(&self) -> &str {
+        match self {
+            WalkEntry::Object(object) => &object.key,
+            WalkEntry::Dir(prefix) => prefix,
+        }
+    }
+}
+
+/// A depth-first walk over a bucket, see [`Bucket::walk`].
+pub struct Walk {
+    bucket: Bucket,
+    delimiter: String,
+    /// The directories being listed, the deepest one is last.
+    levels: Vec<Level>,
+}
+
+struct Level {
+    prefix: String,
+    entries: VecDeque<WalkEntry>,
+    continuation_token: Option<String>,
+    exhausted: bool,
+}
+
+impl Level {
+    fn new(prefix: String) -> Self {
+        Level {
+            prefix,
+            entries: VecDeque::new(),
+            continuation_token: None,
+            exhausted: false,
+        }
+    }
+}
+
+impl Walk {
+    pub(crate) fn new(bucket: Bucket, prefix: String, delimiter: String) -> Self {
+        Walk {
+            bucket,
+            delimiter,
+            levels: vec![Level::new(prefix)],
+        }
+    }
+
+    /// Fetch the next page of the directory.
+    fn fetch(&self, level: &Level) -> Result<(VecDeque<WalkEntry>, Option<String>)> {
+        let mut action = self.bucket.list_action();
+        let prefix = self.bucket.key(&level.prefix);
+        action.with_prefix(prefix.as_ref());
+        if !self.delimiter.is_empty() {
+            action
+                .query_mut()
+                .insert("delimiter", self.delimiter.as_str());
+        }
+        if let Some(token) = &level.continuation_token {
+            action.with_continuation_token(token.as_str());
+        }
+        let response = parse_list_response(self.bucket.client.get(action)?)?;
+
+        // S3 sorts the objects and the common prefixes separately.
+        // A key outside of the prefix of the bucket belongs to someone else.
+        let mut entries: Vec<_> = response
+            .contents
+            .into_iter()
+            .filter_map(|mut object| {
+                object.key = self.bucket.strip_key(&object.key)?.to_string();
+                Some(WalkEntry::Object(object))
+            })
+            .chain(response.common_prefixes.into_iter().filter_map(|common| {
+                let prefix = self.bucket.strip_key(&common.prefix)?;
+                Some(WalkEntry::Dir(prefix.to_string()))
+            }))
+            .collect();
+        entries.sort_by(|a, b| a.key().cmp(b.key()));
+
+        Ok((entries.into(), response.next_continuation_token))
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let level = self.levels.last_mut()?;
+            if let Some(entry) = level.entries.pop_front() {
+                if let WalkEntry::Dir(prefix) = &entry {
+                    self.levels.push(Level::new(prefix.clone()));
+                }
+                return Some(Ok(entry));
+            }
+            if level.exhausted {
+                self.levels.pop();
+                continue;
+            }
+
+            let level = self.levels.last().unwrap();
+            match self.fetch(level) {
+                Ok((entries, continuation_token)) => {
+                    let level = self.levels.last_mut().unwrap();
+                    level.exhausted = continuation_token.is_none();
+                    level.entries = entries;
+                    level.continuation_token = continuation_token;
+                }
+                Err(e) => {
+                    // Skip the directory instead of failing on it forever.
+                    self.levels.pop();
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}